@@ -0,0 +1,1123 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Core day-22 simulation, usable with just `alloc` (embedded/wasm targets)
+//! by disabling the default `std` feature. `main.rs` is the `std`-only
+//! binary front-end (file/argv handling) built on top of this crate.
+
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+
+#[cfg(all(not(feature = "std"), feature = "hashbrown"))]
+extern crate hashbrown;
+
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+#[cfg(feature = "std")]
+use std::collections::HashSet;
+#[cfg(feature = "std")]
+use std::vec::Vec;
+#[cfg(feature = "std")]
+use std::vec;
+#[cfg(feature = "std")]
+use std::vec::IntoIter;
+#[cfg(feature = "std")]
+use std::string::String;
+
+#[cfg(all(not(feature = "std"), feature = "hashbrown"))]
+use hashbrown::HashMap;
+#[cfg(all(not(feature = "std"), feature = "hashbrown"))]
+use hashbrown::HashSet;
+#[cfg(all(not(feature = "std"), not(feature = "hashbrown")))]
+use alloc::collections::BTreeMap as HashMap;
+#[cfg(all(not(feature = "std"), not(feature = "hashbrown")))]
+use alloc::collections::BTreeSet as HashSet;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+#[cfg(not(feature = "std"))]
+use alloc::vec;
+#[cfg(not(feature = "std"))]
+use alloc::vec::IntoIter;
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+
+pub type Position = (i32, i32);
+
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Clone, Copy)]
+pub enum CellState {
+    Clean,
+    Weakened,
+    Infected,
+    Flagged,
+}
+
+use CellState::*;
+
+/// Common surface every grid storage backend exposes to a `Policy`.
+///
+/// `Grid` keeps `Position` keys in a sparse map, while `DenseGrid` packs
+/// cells into a flat bitmap; both implement this trait so `ComputingCluster`
+/// can run over whichever backend the caller picks.
+pub trait GridBackend {
+    fn state(&self, pos: Position) -> CellState;
+    fn clean(&mut self, pos: Position);
+    fn weak(&mut self, pos: Position);
+    fn infect(&mut self, pos: Position);
+    fn flag(&mut self, pos: Position);
+
+    fn reverse(&mut self, pos: Position) -> CellState {
+        match self.state(pos) {
+            Clean => {self.infect(pos); Clean},
+            Infected => {self.clean(pos); Infected},
+            s => s
+        }
+    }
+
+    fn set(&mut self, pos: Position, state: CellState) {
+        match state {
+            Clean => self.clean(pos),
+            Weakened => self.weak(pos),
+            Infected => self.infect(pos),
+            Flagged => self.flag(pos),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Grid(HashMap<Position, CellState>);
+
+impl<S: AsRef<str>> From<S> for Grid {
+    fn from(data: S) -> Self {
+        let lines = data.as_ref().lines().collect::<Vec<_>>();
+        let h = lines.len() as i32;
+        let w = lines[0].len() as i32;
+
+        Grid(
+            lines.iter().enumerate().flat_map(|(r, &l)|
+                l.chars().enumerate()
+                    .filter_map(move |(c, cell)|
+                        if cell == '#' {Some(
+                            ((r as i32 - h / 2, c as i32 - w / 2), Infected)
+                        )
+                        } else { None })
+            ).collect()
+        )
+    }
+}
+
+impl GridBackend for Grid {
+    fn state(&self, pos: Position) -> CellState {
+        self.0.get(&pos).cloned().unwrap_or(Clean)
+    }
+
+    fn clean(&mut self, pos: Position) {
+        self.0.remove(&pos);
+    }
+
+    fn weak(&mut self, pos: Position) {
+        self.0.insert(pos, Weakened);
+    }
+
+    fn infect(&mut self, pos: Position) {
+        self.0.insert(pos, Infected);
+    }
+
+    fn flag(&mut self, pos: Position) {
+        self.0.insert(pos, Flagged);
+    }
+}
+
+/// Per-axis descriptor for `DenseGrid`: `offset` shifts a signed coordinate
+/// into the non-negative range backed by the `Vec`, `size` is how many
+/// coordinates along that axis the `Vec` currently covers.
+#[derive(Debug, Clone, Copy)]
+struct Axis {
+    offset: i32,
+    size: i32,
+}
+
+impl Axis {
+    fn new(offset: i32, size: i32) -> Self {
+        Self { offset, size }
+    }
+
+    fn index(&self, v: i32) -> i32 {
+        v + self.offset
+    }
+
+    fn contains(&self, v: i32) -> bool {
+        let i = self.index(v);
+        i >= 0 && i < self.size
+    }
+
+    /// Smallest axis that still covers the old extent and also `v`.
+    fn grown(&self, v: i32) -> Self {
+        let offset = self.offset.max(-v);
+        let size = (self.size + (offset - self.offset)).max(v + offset + 1);
+        Self::new(offset, size)
+    }
+}
+
+/// Dense `Grid` alternative: cells are packed 2 bits apiece (one of the four
+/// `CellState` variants) into a flat `Vec<u8>`, indexed by a per-axis
+/// `(offset, size)` descriptor instead of hashing `Position`. The backing
+/// store grows on demand whenever a write lands outside the current bounds.
+#[derive(Debug)]
+pub struct DenseGrid {
+    rows: Axis,
+    cols: Axis,
+    cells: Vec<u8>,
+}
+
+impl Default for DenseGrid {
+    fn default() -> Self {
+        Self { rows: Axis::new(0, 0), cols: Axis::new(0, 0), cells: Vec::new() }
+    }
+}
+
+impl DenseGrid {
+    fn code(state: CellState) -> u8 {
+        match state {
+            Clean => 0,
+            Weakened => 1,
+            Infected => 2,
+            Flagged => 3,
+        }
+    }
+
+    fn decode(code: u8) -> CellState {
+        match code {
+            0 => Clean,
+            1 => Weakened,
+            2 => Infected,
+            _ => Flagged,
+        }
+    }
+
+    fn cell_index(&self, pos: Position) -> usize {
+        (self.rows.index(pos.0) * self.cols.size + self.cols.index(pos.1)) as usize
+    }
+
+    fn get_code(&self, index: usize) -> u8 {
+        let byte = self.cells[index / 4];
+        (byte >> ((index % 4) * 2)) & 0b11
+    }
+
+    fn set_code(&mut self, index: usize, code: u8) {
+        let byte = &mut self.cells[index / 4];
+        let shift = (index % 4) * 2;
+        *byte = (*byte & !(0b11 << shift)) | (code << shift);
+    }
+
+    /// Grow the backing store so `pos` falls inside it, shifting every
+    /// existing row into its new location and defaulting fresh cells to
+    /// `Clean`.
+    fn ensure_fits(&mut self, pos: Position) {
+        if self.rows.contains(pos.0) && self.cols.contains(pos.1) {
+            return;
+        }
+
+        let rows = self.rows.grown(pos.0);
+        let cols = self.cols.grown(pos.1);
+
+        let len = ((rows.size * cols.size + 3) / 4) as usize;
+        let mut cells = vec![0u8; len];
+
+        for row in 0..self.rows.size {
+            for col in 0..self.cols.size {
+                let old_index = (row * self.cols.size + col) as usize;
+                let code = self.get_code(old_index);
+                if code != 0 {
+                    let new_row = row + rows.offset - self.rows.offset;
+                    let new_col = col + cols.offset - self.cols.offset;
+                    let new_index = (new_row * cols.size + new_col) as usize;
+                    let byte = &mut cells[new_index / 4];
+                    let shift = (new_index % 4) * 2;
+                    *byte |= code << shift;
+                }
+            }
+        }
+
+        self.rows = rows;
+        self.cols = cols;
+        self.cells = cells;
+    }
+}
+
+impl GridBackend for DenseGrid {
+    fn state(&self, pos: Position) -> CellState {
+        if !self.rows.contains(pos.0) || !self.cols.contains(pos.1) {
+            return Clean;
+        }
+        let index = self.cell_index(pos);
+        Self::decode(self.get_code(index))
+    }
+
+    fn clean(&mut self, pos: Position) {
+        self.ensure_fits(pos);
+        let index = self.cell_index(pos);
+        self.set_code(index, Self::code(Clean));
+    }
+
+    fn weak(&mut self, pos: Position) {
+        self.ensure_fits(pos);
+        let index = self.cell_index(pos);
+        self.set_code(index, Self::code(Weakened));
+    }
+
+    fn infect(&mut self, pos: Position) {
+        self.ensure_fits(pos);
+        let index = self.cell_index(pos);
+        self.set_code(index, Self::code(Infected));
+    }
+
+    fn flag(&mut self, pos: Position) {
+        self.ensure_fits(pos);
+        let index = self.cell_index(pos);
+        self.set_code(index, Self::code(Flagged));
+    }
+}
+
+
+pub enum Direction {
+    Up,
+    Right,
+    Left,
+    Down,
+}
+
+use Direction::*;
+
+pub struct Currier {
+    position: Position,
+    direction: Direction,
+}
+
+impl Default for Currier {
+    fn default() -> Self {
+        Self::new(Default::default(), Up)
+    }
+}
+
+impl Currier {
+    pub fn new(start: Position, direction: Direction) -> Self {
+        Self { position: start, direction }
+    }
+
+    pub fn step(&mut self) -> Position {
+        match self.direction {
+            Up => self.position.0 -= 1,
+            Right => self.position.1 += 1,
+            Left => self.position.1 -= 1,
+            Down => self.position.0 += 1,
+        }
+
+        self.position
+    }
+
+    pub fn right(&mut self) {
+        match self.direction {
+            Up => self.direction = Right,
+            Right => self.direction = Down,
+            Left => self.direction = Up,
+            Down => self.direction = Left,
+        }
+    }
+
+    pub fn left(&mut self) {
+        match self.direction {
+            Up => self.direction = Left,
+            Right => self.direction = Up,
+            Left => self.direction = Down,
+            Down => self.direction = Right,
+        }
+    }
+
+    pub fn u_turn(&mut self) {
+        self.right();
+        self.right();
+    }
+
+    pub fn turn(&mut self, turn: Turn) {
+        match turn {
+            Turn::Left => self.left(),
+            Turn::Right => self.right(),
+            Turn::Straight => {},
+            Turn::Reverse | Turn::UTurn => self.u_turn(),
+        }
+    }
+}
+
+/// A turn a `TablePolicy` can apply to a `Currier`. `Reverse` and `UTurn`
+/// are kept as distinct names for readability at the call site but both
+/// turn the currier all the way around (two `right()`s).
+#[derive(Debug, Clone, Copy)]
+pub enum Turn {
+    Left,
+    Right,
+    Straight,
+    Reverse,
+    UTurn,
+}
+
+pub trait Policy {
+    fn apply(&self, grid: &mut impl GridBackend, currier: &mut Currier);
+}
+
+pub struct CurrierRule {}
+
+impl Default for CurrierRule {
+    fn default() -> Self { Self::new() }
+}
+
+impl CurrierRule {
+    pub fn new() -> Self { Self {} }
+}
+
+impl Policy for CurrierRule {
+    fn apply(&self, grid: &mut impl GridBackend, currier: &mut Currier) {
+        let old_state = grid.reverse(currier.position);
+        match old_state {
+            Clean => currier.left(),
+            Infected => currier.right(),
+            _ => panic!("Not implemented : this policy cannot work with this kind of states")
+        }
+        currier.step();
+    }
+}
+
+pub struct EvolvedRule {}
+
+impl Default for EvolvedRule {
+    fn default() -> Self { Self::new() }
+}
+
+impl EvolvedRule {
+    pub fn new() -> Self { Self {} }
+}
+
+impl Policy for EvolvedRule {
+    fn apply(&self, grid: &mut impl GridBackend, currier: &mut Currier) {
+        let old_state = grid.state(currier.position);
+        let position = currier.position;
+        match old_state {
+            Clean => {currier.left(); grid.weak(position)},
+            Infected => {currier.right(); grid.flag(position)},
+            Weakened => {grid.infect(position)},
+            Flagged => {currier.right();currier.right(); grid.clean(position)},
+        };
+        currier.step();
+    }
+}
+
+/// A `Policy` driven entirely by a `CellState -> (Turn, CellState)` table
+/// instead of hand-written match arms, so custom virus variants (extra
+/// states, unusual turn patterns) don't need a new `Policy` impl.
+pub struct TablePolicy {
+    table: HashMap<CellState, (Turn, CellState)>,
+}
+
+impl TablePolicy {
+    pub fn new(table: HashMap<CellState, (Turn, CellState)>) -> Self {
+        Self { table }
+    }
+
+    /// Reproduces `CurrierRule`'s behaviour as a table.
+    pub fn currier_rule() -> Self {
+        let mut table = HashMap::new();
+        table.insert(Clean, (Turn::Left, Infected));
+        table.insert(Infected, (Turn::Right, Clean));
+        Self::new(table)
+    }
+
+    /// Reproduces `EvolvedRule`'s behaviour as a table.
+    pub fn evolved_rule() -> Self {
+        let mut table = HashMap::new();
+        table.insert(Clean, (Turn::Left, Weakened));
+        table.insert(Weakened, (Turn::Straight, Infected));
+        table.insert(Infected, (Turn::Right, Flagged));
+        table.insert(Flagged, (Turn::UTurn, Clean));
+        Self::new(table)
+    }
+}
+
+impl Policy for TablePolicy {
+    fn apply(&self, grid: &mut impl GridBackend, currier: &mut Currier) {
+        let position = currier.position;
+        let &(turn, next_state) = self.table.get(&grid.state(position))
+            .unwrap_or_else(|| panic!("TablePolicy has no rule for this cell state"));
+
+        currier.turn(turn);
+        grid.set(position, next_state);
+        currier.step();
+    }
+}
+
+
+
+pub struct ComputingCluster<P: Policy, G: GridBackend> {
+    grid: G,
+    currier: Currier,
+    policy: P
+}
+
+impl<P: Policy, G: GridBackend> ComputingCluster<P, G> {
+    pub fn new(grid: G, currier: Currier, policy: P) -> Self {
+        ComputingCluster { grid, currier, policy }
+    }
+}
+
+impl<P: Policy, G: GridBackend> Iterator for ComputingCluster<P, G> {
+    type Item = (Position, CellState);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let position = self.currier.position;
+        self.policy.apply(&mut self.grid, &mut self.currier);
+
+        Some((position, self.grid.state(position)))
+    }
+}
+
+pub fn infections(cluster: ComputingCluster<impl Policy, impl GridBackend>, steps: usize) -> usize {
+    cluster.take(steps).filter(|&(_, s)| s == Infected).count()
+}
+
+/// Coordinate for the neighbor-count automaton below: a fixed-length array
+/// of axis values. Implemented for `[i32; 2]`, `[i32; 3]` and `[i32; 4]` so
+/// `generation` runs the same engine in 2D, 3D and 4D.
+pub trait Coord: Copy + Eq + Ord + core::hash::Hash {
+    /// Every non-zero offset in `{-1, 0, 1}` on each axis, i.e. the
+    /// neighborhood: `3^N - 1` of them (8 in 2D, 26 in 3D, 80 in 4D).
+    fn neighbor_offsets() -> Vec<Self>;
+
+    fn translated(&self, offset: &Self) -> Self;
+}
+
+macro_rules! impl_coord {
+    ($n:expr) => {
+        impl Coord for [i32; $n] {
+            fn neighbor_offsets() -> Vec<Self> {
+                let mut offsets = Vec::new();
+                let mut current = [-1; $n];
+                loop {
+                    if current != [0; $n] {
+                        offsets.push(current);
+                    }
+
+                    let mut axis = 0;
+                    loop {
+                        if axis == $n {
+                            return offsets;
+                        }
+                        current[axis] += 1;
+                        if current[axis] > 1 {
+                            current[axis] = -1;
+                            axis += 1;
+                        } else {
+                            break;
+                        }
+                    }
+                }
+            }
+
+            fn translated(&self, offset: &Self) -> Self {
+                let mut result = [0; $n];
+                for axis in 0..$n {
+                    result[axis] = self[axis] + offset[axis];
+                }
+                result
+            }
+        }
+    };
+}
+
+impl_coord!(2);
+impl_coord!(3);
+impl_coord!(4);
+
+/// Sparse-set storage for the neighbor-count automaton: only the non-`Clean`
+/// cells are kept, same idea as `Grid` but keyed on a dimension-generic
+/// `Coord` instead of the carrier's `Position`.
+#[derive(Debug)]
+pub struct Field<C: Coord>(HashMap<C, CellState>);
+
+impl<C: Coord> Default for Field<C> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<C: Coord> Field<C> {
+    pub fn new() -> Self {
+        Field(Default::default())
+    }
+
+    pub fn state(&self, pos: C) -> CellState {
+        self.0.get(&pos).cloned().unwrap_or(Clean)
+    }
+
+    pub fn set(&mut self, pos: C, state: CellState) {
+        if state == Clean {
+            self.0.remove(&pos);
+        } else {
+            self.0.insert(pos, state);
+        }
+    }
+}
+
+/// Next-state rule for the neighbor-count automaton: every cell advances at
+/// once based only on its own state and how many of its neighbors are
+/// `Infected`, unlike `Policy` which follows a single `Currier`.
+pub trait StepRule {
+    fn next(&self, current: CellState, infected_neighbors: usize) -> CellState;
+}
+
+/// The classic Game-of-Life rule (birth on 3, survival on 2 or 3), treating
+/// `Infected` as alive and `Clean` as dead.
+pub struct GameOfLife;
+
+impl StepRule for GameOfLife {
+    fn next(&self, current: CellState, infected_neighbors: usize) -> CellState {
+        match (current, infected_neighbors) {
+            (Infected, 2) | (Infected, 3) => Infected,
+            (Clean, 3) => Infected,
+            _ => Clean,
+        }
+    }
+}
+
+/// Advance every cell of `field` one tick under `rule`. The candidate region
+/// is the active cells plus everything one step away in every direction, so
+/// growth at the boundary is captured.
+pub fn generation<C: Coord, R: StepRule>(field: &Field<C>, rule: &R) -> Field<C> {
+    let offsets = C::neighbor_offsets();
+
+    let mut candidates = HashSet::new();
+    for &pos in field.0.keys() {
+        candidates.insert(pos);
+        for offset in &offsets {
+            candidates.insert(pos.translated(offset));
+        }
+    }
+
+    let mut next = Field::new();
+    for pos in candidates {
+        let infected_neighbors = offsets.iter()
+            .filter(|offset| field.state(pos.translated(offset)) == Infected)
+            .count();
+
+        next.set(pos, rule.next(field.state(pos), infected_neighbors));
+    }
+    next
+}
+
+/// (row, col) delta for a `ScriptedCarrier`'s facing, distinct from the
+/// carrier model's `Direction` enum: rotating always yields one of
+/// `(0, 1)` right, `(1, 0)` down, `(0, -1)` left, `(-1, 0)` up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Heading(i32, i32);
+
+impl Heading {
+    pub const RIGHT: Heading = Heading(0, 1);
+
+    pub fn rotate_right(&self) -> Heading {
+        Heading(self.1, -self.0)
+    }
+
+    pub fn rotate_left(&self) -> Heading {
+        Heading(-self.1, self.0)
+    }
+
+    pub fn facing_index(&self) -> i32 {
+        match (self.0, self.1) {
+            (0, 1) => 0,
+            (1, 0) => 1,
+            (0, -1) => 2,
+            (-1, 0) => 3,
+            _ => unreachable!("Heading is always axis-aligned"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cell {
+    Open,
+    Wall,
+}
+
+/// A rectangular map for the scripted-carrier mode: cells are `Open` or
+/// `Wall`, and anything not present in the sparse map (ragged rows, the
+/// blank padding between map blocks) is off-map "void".
+#[derive(Debug)]
+pub struct BoundedGrid(HashMap<Position, Cell>);
+
+impl<S: AsRef<str>> From<S> for BoundedGrid {
+    fn from(data: S) -> Self {
+        BoundedGrid(
+            data.as_ref().lines().enumerate().flat_map(|(r, line)|
+                line.chars().enumerate().filter_map(move |(c, ch)| match ch {
+                    '.' => Some(((r as i32, c as i32), Cell::Open)),
+                    '#' => Some(((r as i32, c as i32), Cell::Wall)),
+                    _ => None,
+                })
+            ).collect()
+        )
+    }
+}
+
+impl BoundedGrid {
+    pub fn cell(&self, pos: Position) -> Option<Cell> {
+        self.0.get(&pos).cloned()
+    }
+
+    pub fn leftmost_open_in_row(&self, row: i32) -> Position {
+        self.0.iter()
+            .filter(|&(&(r, _), &cell)| r == row && cell == Cell::Open)
+            .map(|(&pos, _)| pos)
+            .min_by_key(|&(_, c)| c)
+            .expect("row has no open cell")
+    }
+
+    /// The cell you land on after stepping off the map heading in
+    /// `direction` from `pos`: walk backwards until falling off the
+    /// opposite edge, i.e. the farthest cell reachable that way.
+    fn wrap(&self, pos: Position, direction: Heading) -> Position {
+        let mut candidate = pos;
+        loop {
+            let prev = (candidate.0 - direction.0, candidate.1 - direction.1);
+            if self.cell(prev).is_none() {
+                return candidate;
+            }
+            candidate = prev;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Instruction {
+    Forward(u32),
+    TurnLeft,
+    TurnRight,
+}
+
+pub fn parse_instructions<S: AsRef<str>>(data: S) -> Vec<Instruction> {
+    let mut instructions = Vec::new();
+    let mut digits = String::new();
+
+    for ch in data.as_ref().trim().chars() {
+        match ch {
+            'L' | 'R' => {
+                if !digits.is_empty() {
+                    instructions.push(Instruction::Forward(digits.parse().unwrap()));
+                    digits.clear();
+                }
+                instructions.push(if ch == 'L' { Instruction::TurnLeft } else { Instruction::TurnRight });
+            }
+            digit => digits.push(digit),
+        }
+    }
+    if !digits.is_empty() {
+        instructions.push(Instruction::Forward(digits.parse().unwrap()));
+    }
+
+    instructions
+}
+
+/// Walks a `BoundedGrid` according to a scripted instruction stream,
+/// blocked by `Wall`s and wrapping around the finite map edges (skipping
+/// void cells) instead of the carrier model's infinite sparse `Grid`.
+pub struct ScriptedCarrier<'a> {
+    grid: &'a BoundedGrid,
+    position: Position,
+    heading: Heading,
+    instructions: IntoIter<Instruction>,
+}
+
+impl<'a> ScriptedCarrier<'a> {
+    pub fn new(grid: &'a BoundedGrid, instructions: Vec<Instruction>) -> Self {
+        let position = grid.leftmost_open_in_row(0);
+        Self { grid, position, heading: Heading::RIGHT, instructions: instructions.into_iter() }
+    }
+
+    pub fn execute(&mut self, instruction: Instruction) {
+        match instruction {
+            Instruction::TurnLeft => self.heading = self.heading.rotate_left(),
+            Instruction::TurnRight => self.heading = self.heading.rotate_right(),
+            Instruction::Forward(n) => {
+                for _ in 0..n {
+                    let mut next = (self.position.0 + self.heading.0, self.position.1 + self.heading.1);
+                    if self.grid.cell(next).is_none() {
+                        next = self.grid.wrap(self.position, self.heading);
+                    }
+                    match self.grid.cell(next) {
+                        Some(Cell::Wall) => break,
+                        Some(Cell::Open) => self.position = next,
+                        None => unreachable!("wrap always lands on an existing cell"),
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<'a> Iterator for ScriptedCarrier<'a> {
+    type Item = (i32, i32, i32);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let instruction = self.instructions.next()?;
+        self.execute(instruction);
+        Some((self.position.0, self.position.1, self.heading.facing_index()))
+    }
+}
+
+pub fn final_score(carrier: ScriptedCarrier) -> i32 {
+    let (row, col, facing) = carrier.last().expect("at least one instruction");
+    1000 * row + 4 * col + facing
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    static SIMPLE: &str = "\
+                                    ..#\n\
+                                    #..\n\
+                                    ...\
+                                    ";
+
+    #[test]
+    fn grid_cluster_query() {
+        let grid = Grid::from(SIMPLE);
+
+        assert_eq!(Clean, grid.state((0, 0)));
+        assert_eq!(Infected, grid.state((0, -1)));
+        assert_eq!(Infected, grid.state((-1, 1)));
+        assert_eq!(Clean, grid.state((1, 1)));
+        assert_eq!(Clean, grid.state((100, -32)));
+    }
+
+    #[test]
+    fn grid_clean() {
+        let mut grid = Grid::from(SIMPLE);
+
+        grid.clean((0, -1));
+
+        assert_eq!(Clean, grid.state((0, -1)));
+
+        grid.clean((0, 0));
+
+        assert_eq!(Clean, grid.state((0, 0)))
+    }
+
+    #[test]
+    fn grid_infect() {
+        let mut grid = Grid::from(SIMPLE);
+
+        grid.infect((0, 0));
+
+        assert_eq!(Infected, grid.state((0, 0)));
+
+        grid.infect((0, -1));
+
+        assert_eq!(Infected, grid.state((0, -1)))
+    }
+
+    #[test]
+    fn grid_reverse() {
+        let mut grid = Grid::from(SIMPLE);
+
+        assert_eq!(Clean, grid.reverse((0, 0)));
+
+        assert_eq!(Infected, grid.state((0, 0)));
+
+        assert_eq!(Infected, grid.reverse((0, 0)));
+
+        assert_eq!(Clean, grid.state((0, 0)));
+    }
+
+    #[test]
+    fn dense_grid_starts_clean() {
+        let grid = DenseGrid::default();
+
+        assert_eq!(Clean, grid.state((0, 0)));
+        assert_eq!(Clean, grid.state((100, -32)));
+    }
+
+    #[test]
+    fn dense_grid_infect_and_clean() {
+        let mut grid = DenseGrid::default();
+
+        grid.infect((0, 0));
+        assert_eq!(Infected, grid.state((0, 0)));
+
+        grid.clean((0, 0));
+        assert_eq!(Clean, grid.state((0, 0)));
+    }
+
+    #[test]
+    fn dense_grid_reverse() {
+        let mut grid = DenseGrid::default();
+
+        assert_eq!(Clean, grid.reverse((0, 0)));
+        assert_eq!(Infected, grid.state((0, 0)));
+
+        assert_eq!(Infected, grid.reverse((0, 0)));
+        assert_eq!(Clean, grid.state((0, 0)));
+    }
+
+    #[test]
+    fn dense_grid_grows_in_every_direction() {
+        let mut grid = DenseGrid::default();
+
+        grid.infect((5, 5));
+        grid.infect((-5, -5));
+        grid.infect((5, -5));
+        grid.infect((-5, 5));
+
+        assert_eq!(Infected, grid.state((5, 5)));
+        assert_eq!(Infected, grid.state((-5, -5)));
+        assert_eq!(Infected, grid.state((5, -5)));
+        assert_eq!(Infected, grid.state((-5, 5)));
+        assert_eq!(Clean, grid.state((0, 0)));
+    }
+
+    #[test]
+    fn count_infections_with_dense_grid() {
+        let mut grid = DenseGrid::default();
+        for (pos, state) in Grid::from(SIMPLE).0.into_iter() {
+            if state == Infected {
+                grid.infect(pos)
+            }
+        }
+
+        let cluster = ComputingCluster::new(grid, Default::default(), CurrierRule::new());
+
+        assert_eq!(41, infections(cluster, 70))
+    }
+
+    #[test]
+    fn count_infections_evolved_virus_with_dense_grid() {
+        let mut grid = DenseGrid::default();
+        for (pos, state) in Grid::from(SIMPLE).0.into_iter() {
+            grid.set(pos, state);
+        }
+
+        let cluster = ComputingCluster::new(grid, Default::default(), EvolvedRule::new());
+
+        assert_eq!(26, infections(cluster, 100))
+    }
+
+
+    #[test]
+    fn currier_moves() {
+        let mut currier = Currier::new((0, 0), Up);
+
+        assert_eq!((-1, 0), currier.step());
+
+        currier.right();
+
+        assert_eq!((-1, 1), currier.step());
+
+        currier.left();
+
+        assert_eq!((-2, 1), currier.step());
+    }
+
+    #[test]
+    fn policy_currier() {
+        let grid = Grid::from(SIMPLE);
+        let currier = Currier::default();
+
+        let policy = CurrierRule {};
+
+        let mut cluster = ComputingCluster::new(grid, currier, policy);
+
+        assert_eq!(((0, 0), Infected), cluster.next().unwrap());
+        assert_eq!(((0, -1), Clean), cluster.next().unwrap());
+        assert_eq!(((-1, -1), Infected), cluster.next().unwrap());
+        assert_eq!(((-1, -2), Infected), cluster.next().unwrap());
+        assert_eq!(((0, -2), Infected), cluster.next().unwrap());
+        assert_eq!(((0, -1), Infected), cluster.next().unwrap());
+        assert_eq!(((-1, -1), Clean), cluster.next().unwrap());
+    }
+
+    #[test]
+    fn count_infections() {
+        let cluster = ComputingCluster::new(
+            Grid::from(SIMPLE), Default::default(), CurrierRule::new()
+        );
+
+        assert_eq!(41, infections(cluster, 70))
+
+    }
+
+    #[test]
+    fn count_lot_of_infections() {
+        let cluster = ComputingCluster::new(
+            Grid::from(SIMPLE), Default::default(), CurrierRule::new()
+        );
+
+        assert_eq!(5587, infections(cluster, 10000))
+
+    }
+
+    #[test]
+    fn evolved_policy() {
+        let mut grid = Grid::from(SIMPLE);
+        let mut currier = Currier::default();
+
+        let policy = EvolvedRule::new();
+
+        // Clean
+        policy.apply(&mut grid, &mut currier);
+
+        assert_eq!(Weakened, grid.state((0,0)));
+        assert_eq!((0, -1), currier.position);
+
+        let mut currier = Currier::default();
+
+        // Weakened
+        policy.apply(&mut grid, &mut currier);
+
+        assert_eq!(Infected, grid.state((0,0)));
+        assert_eq!((-1, 0), currier.position);
+
+        let mut currier = Currier::default();
+
+        // Infected
+        policy.apply(&mut grid, &mut currier);
+
+        assert_eq!(Flagged, grid.state((0,0)));
+        assert_eq!((0, 1), currier.position);
+
+        let mut currier = Currier::default();
+
+        // Flagged
+        policy.apply(&mut grid, &mut currier);
+
+        assert_eq!(Clean, grid.state((0,0)));
+        assert_eq!((1, 0), currier.position);
+    }
+
+    #[test]
+    fn count_infections_evolved_virus() {
+        let cluster = ComputingCluster::new(
+            Grid::from(SIMPLE), Default::default(), EvolvedRule::new()
+        );
+
+        assert_eq!(26, infections(cluster, 100))
+
+    }
+
+    #[test]
+    fn table_policy_reproduces_currier_rule() {
+        let cluster = ComputingCluster::new(
+            Grid::from(SIMPLE), Default::default(), TablePolicy::currier_rule()
+        );
+
+        assert_eq!(41, infections(cluster, 70))
+    }
+
+    #[test]
+    fn table_policy_reproduces_evolved_rule() {
+        let cluster = ComputingCluster::new(
+            Grid::from(SIMPLE), Default::default(), TablePolicy::evolved_rule()
+        );
+
+        assert_eq!(26, infections(cluster, 100))
+    }
+
+    #[test]
+    fn table_policy_supports_custom_states() {
+        let mut table = HashMap::new();
+        table.insert(Clean, (Turn::Straight, Infected));
+        table.insert(Infected, (Turn::UTurn, Clean));
+
+        let cluster = ComputingCluster::new(
+            Grid::from(SIMPLE), Default::default(), TablePolicy::new(table)
+        );
+
+        assert_eq!(((0, 0), Infected), cluster.take(1).next().unwrap());
+    }
+
+    #[test]
+    fn count_infections_evolved_virus_10000000() {
+        let cluster = ComputingCluster::new(
+            Grid::from(SIMPLE), Default::default(), EvolvedRule::new()
+        );
+
+        assert_eq!(2511944, infections(cluster, 10000000))
+
+    }
+
+    #[test]
+    fn coord_2d_neighbor_offsets() {
+        let offsets = <[i32; 2]>::neighbor_offsets();
+
+        assert_eq!(8, offsets.len());
+        assert!(!offsets.contains(&[0, 0]));
+        assert!(offsets.contains(&[1, 1]));
+        assert!(offsets.contains(&[-1, 0]));
+    }
+
+    #[test]
+    fn coord_3d_and_4d_neighbor_counts() {
+        assert_eq!(26, <[i32; 3]>::neighbor_offsets().len());
+        assert_eq!(80, <[i32; 4]>::neighbor_offsets().len());
+    }
+
+    #[test]
+    fn game_of_life_blinker_oscillates() {
+        let mut field = Field::new();
+        for pos in [[1, 0], [1, 1], [1, 2]].iter() {
+            field.set(*pos, Infected);
+        }
+
+        let next = generation(&field, &GameOfLife);
+
+        assert_eq!(Clean, next.state([1, 0]));
+        assert_eq!(Infected, next.state([0, 1]));
+        assert_eq!(Infected, next.state([1, 1]));
+        assert_eq!(Infected, next.state([2, 1]));
+        assert_eq!(Clean, next.state([1, 2]));
+
+        let back = generation(&next, &GameOfLife);
+
+        assert_eq!(Infected, back.state([1, 0]));
+        assert_eq!(Infected, back.state([1, 1]));
+        assert_eq!(Infected, back.state([1, 2]));
+    }
+
+    static MAP: &str = "        ...#\n        .#..\n        #...\n        ....\n...#.......#\n........#...\n..#....#....\n..........#.\n        ...#....\n        .....#..\n        .#..\n        ..........";
+
+    static PATH: &str = "10R5L5R10L4R5L5";
+
+    #[test]
+    fn heading_rotates_through_all_facings() {
+        let right = Heading::RIGHT;
+        let down = right.rotate_right();
+        let left = down.rotate_right();
+        let up = left.rotate_right();
+
+        assert_eq!(0, right.facing_index());
+        assert_eq!(1, down.facing_index());
+        assert_eq!(2, left.facing_index());
+        assert_eq!(3, up.facing_index());
+        assert_eq!(right, up.rotate_right());
+        assert_eq!(right, down.rotate_left());
+    }
+
+    #[test]
+    fn bounded_grid_starts_at_leftmost_open_cell() {
+        let grid = BoundedGrid::from(MAP);
+
+        assert_eq!((0, 8), grid.leftmost_open_in_row(0));
+    }
+
+    #[test]
+    fn scripted_carrier_follows_wall_and_wraps() {
+        let grid = BoundedGrid::from(MAP);
+        let instructions = parse_instructions(PATH);
+
+        let carrier = ScriptedCarrier::new(&grid, instructions);
+
+        assert_eq!(5028, final_score(carrier));
+    }
+}